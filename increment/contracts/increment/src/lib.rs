@@ -1,36 +1,288 @@
-#![no_std]
-
-use soroban_sdk::{contract, contractimpl, log, symbol_short, Env, Symbol};
-
-// Define a constant key for storing the counter in contract storage.
-const COUNTER: Symbol = symbol_short!("COUNTER");
-
-#[contract]
-pub struct IncrementContract;
-
-#[contractimpl]
-impl IncrementContract {
-    /// Increments an internal counter and returns the new value.
-    ///
-    /// # Arguments
-    /// * `env` - The environment object, which provides access to storage and other blockchain features.
-    pub fn increment(env: Env) -> u32 {
-        // Try to get the current counter value from storage. If it doesn't exist, start at 0.
-        let mut count: u32 = env.storage().instance().get(&COUNTER).unwrap_or(0);
-        // Log the current count (before incrementing) for debugging and transparency.
-        log!(&env, "count: {}", count);
-
-        // Increment the counter by 1.
-        count += 1;
-        // Store the new counter value back into contract storage.
-        env.storage().instance().set(&COUNTER, &count);
-        // Extend the time-to-live (TTL) for this storage entry, so it persists for a while.
-        env.storage().instance().extend_ttl(50, 100);
-
-        // Return the new counter value.
-        count
-    }
-}
-
-// The test module is included below, but only compiled when running tests.
-mod test;
\ No newline at end of file
+#![no_std]
+
+use soroban_sdk::{
+    contract, contractimpl, contracttype, log, symbol_short, Address, BytesN, Env, Symbol,
+};
+
+// Storage keys for this contract. Using a `#[contracttype]` enum instead of a
+// bare `Symbol` lets us key the counter per-caller, so each user gets their
+// own independent tally instead of everyone sharing one global `COUNTER`.
+#[contracttype]
+pub enum DataKey {
+    Counter(Address),
+    Admin,
+    StorageConfig,
+}
+
+// The bare `Symbol` the very first version of this contract stored its one
+// global counter under, in `instance()` storage. Kept around so a deployment
+// from before the per-user rewrite can still recover that value - see
+// `migrate_legacy_counter`.
+const LEGACY_COUNTER: Symbol = symbol_short!("COUNTER");
+
+// The state kept for each user's counter. Beyond the running total, we keep
+// a little history - the size of the last increment and the ledger it was
+// applied on - so clients can tell not just the current value but how and
+// when it last changed, without having to replay every past call.
+#[contracttype]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct CounterState {
+    pub count: u32,
+    pub last_incr: u32,
+    pub updated_ledger: u32,
+}
+
+// The storage policy for counter entries: the TTL bump to apply (`threshold`,
+// `extend_to` - previously hardcoded as `extend_ttl(50, 100)`), and which
+// durability tier counters live under. `persistent()` survives independently
+// of the contract instance but costs more to keep alive long-term;
+// `instance()` is cheaper to bump (one TTL call covers every counter) but
+// ties every counter's lifetime to the contract instance's own. Making both
+// admin-settable lets a long-lived deployment tune its durability without a
+// code upgrade.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StorageConfig {
+    pub threshold: u32,
+    pub extend_to: u32,
+    pub counters_persistent: bool,
+}
+
+impl StorageConfig {
+    const DEFAULT: StorageConfig = StorageConfig {
+        threshold: 50,
+        extend_to: 100,
+        counters_persistent: true,
+    };
+}
+
+#[contract]
+pub struct IncrementContract;
+
+#[contractimpl]
+impl IncrementContract {
+    /// One-time setup that records the contract's admin. Must be called once,
+    /// before `upgrade`, right after deployment. Panics if an admin has
+    /// already been set, so a deployed contract can't be silently re-claimed
+    /// by a later caller.
+    pub fn init(env: Env, admin: Address) {
+        if env.storage().instance().has(&DataKey::Admin) {
+            panic!("already initialized");
+        }
+        env.storage().instance().set(&DataKey::Admin, &admin);
+    }
+
+    /// Upgrades the contract's executable to `new_wasm_hash`, keeping all
+    /// existing storage (and therefore every user's counter) intact. Only
+    /// the admin recorded by `init` may do this.
+    pub fn upgrade(env: Env, new_wasm_hash: BytesN<32>) {
+        let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        admin.require_auth();
+
+        env.deployer().update_current_contract_wasm(new_wasm_hash);
+    }
+
+    /// Sets the TTL policy applied to counter entries on every write.
+    /// Restricted to the admin recorded by `init`.
+    pub fn set_storage_config(env: Env, config: StorageConfig) {
+        let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        admin.require_auth();
+
+        env.storage()
+            .instance()
+            .set(&DataKey::StorageConfig, &config);
+    }
+
+    /// Increments `user`'s personal counter by 1 and returns the new state.
+    /// A thin wrapper over `increment_by` for the common case.
+    pub fn increment(env: Env, user: Address) -> CounterState {
+        Self::increment_by(env, user, 1)
+    }
+
+    /// Increments `user`'s personal counter by `step` and returns the new state.
+    ///
+    /// Uses checked arithmetic: if `step` would overflow the counter's `u32`,
+    /// the call panics rather than silently wrapping around to a small value.
+    ///
+    /// # Arguments
+    /// * `env` - The environment object, which provides access to storage and other blockchain features.
+    /// * `user` - The address whose counter should be incremented. Must authorize this call.
+    /// * `step` - The amount to add to the counter.
+    pub fn increment_by(env: Env, user: Address, step: u32) -> CounterState {
+        // Require the caller to have authorized this invocation. The Soroban
+        // host enforces the signature check and prevents the auth from being
+        // replayed for a different invocation.
+        user.require_auth();
+
+        let key = DataKey::Counter(user);
+        let config = Self::load_config(&env);
+
+        // Try to get the current counter state from storage. If it doesn't
+        // exist, fall back to a pre-rewrite deployment's legacy global
+        // counter (if any), then finally to zeroed.
+        let mut state = match Self::get_counter(&env, &key, &config) {
+            Some(state) => state,
+            None => Self::migrate_legacy_counter(&env).unwrap_or_default(),
+        };
+        // Log the current count (before incrementing) for debugging and transparency.
+        log!(&env, "count: {}", state.count);
+
+        // Apply the increment and record how and when it happened.
+        state.count = state
+            .count
+            .checked_add(step)
+            .expect("counter overflow: increment would exceed u32::MAX");
+        state.last_incr = step;
+        state.updated_ledger = env.ledger().sequence();
+
+        // Store the new counter state back into contract storage.
+        Self::set_counter(&env, &key, &state, &config);
+        // Extend the entry's TTL per the configured storage policy, so it
+        // persists for a while instead of being tied to a hardcoded bump.
+        // This also refreshes the contract's own instance entry (Admin,
+        // StorageConfig), which would otherwise archive on its own schedule
+        // even while individual counters stay live.
+        Self::bump_ttl(&env, &key, &config);
+
+        // Publish an event so off-chain indexers and frontends can react to
+        // the new count over RPC instead of having to poll the contract.
+        env.events()
+            .publish((symbol_short!("incr"), symbol_short!("count")), state.count);
+
+        // Return the new counter state.
+        state
+    }
+
+    /// Decrements `user`'s personal counter by 1, saturating at 0 instead of
+    /// underflowing. Returns the new count.
+    pub fn decrement(env: Env, user: Address) -> u32 {
+        user.require_auth();
+
+        let key = DataKey::Counter(user);
+        let config = Self::load_config(&env);
+
+        let mut state = Self::load_state(&env, &key, &config);
+        state.count = state.count.saturating_sub(1);
+        state.updated_ledger = env.ledger().sequence();
+
+        Self::set_counter(&env, &key, &state, &config);
+        Self::bump_ttl(&env, &key, &config);
+
+        state.count
+    }
+
+    /// Resets `user`'s counter back to zero. Restricted to the admin
+    /// recorded by `init`.
+    pub fn reset(env: Env, user: Address) {
+        let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        admin.require_auth();
+
+        let key = DataKey::Counter(user);
+        let config = Self::load_config(&env);
+        Self::set_counter(
+            &env,
+            &key,
+            &CounterState {
+                count: 0,
+                last_incr: 0,
+                updated_ledger: env.ledger().sequence(),
+            },
+            &config,
+        );
+        Self::bump_ttl(&env, &key, &config);
+    }
+
+    /// Returns `user`'s current counter state without modifying it.
+    pub fn get_state(env: Env, user: Address) -> CounterState {
+        let config = Self::load_config(&env);
+        Self::load_state(&env, &DataKey::Counter(user), &config)
+    }
+
+    /// Loads `key`'s counter state as currently persisted under whichever
+    /// tier `config` designates, defaulting to zeroed if it has never been
+    /// set. Does not consult or mutate the legacy global counter - see
+    /// `migrate_legacy_counter`, which only the `increment_by` write path
+    /// invokes.
+    fn load_state(env: &Env, key: &DataKey, config: &StorageConfig) -> CounterState {
+        Self::get_counter(env, key, config).unwrap_or_default()
+    }
+
+    /// Reads `key`'s counter entry from the tier `config.counters_persistent`
+    /// designates, falling back to the other tier so a counter written
+    /// before an admin flips the toggle isn't stranded and unreadable.
+    fn get_counter(env: &Env, key: &DataKey, config: &StorageConfig) -> Option<CounterState> {
+        if config.counters_persistent {
+            env.storage()
+                .persistent()
+                .get(key)
+                .or_else(|| env.storage().instance().get(key))
+        } else {
+            env.storage()
+                .instance()
+                .get(key)
+                .or_else(|| env.storage().persistent().get(key))
+        }
+    }
+
+    /// Writes `key`'s counter entry to the tier `config.counters_persistent`
+    /// designates. Does not clear the other tier - a toggle flip is picked up
+    /// for reads via `get_counter`'s fallback, and the stale copy ages out on
+    /// its own TTL.
+    fn set_counter(env: &Env, key: &DataKey, state: &CounterState, config: &StorageConfig) {
+        if config.counters_persistent {
+            env.storage().persistent().set(key, state);
+        } else {
+            env.storage().instance().set(key, state);
+        }
+    }
+
+    /// Loads the admin-configured storage policy, falling back to
+    /// `StorageConfig::DEFAULT` if none has been set.
+    fn load_config(env: &Env) -> StorageConfig {
+        env.storage()
+            .instance()
+            .get(&DataKey::StorageConfig)
+            .unwrap_or(StorageConfig::DEFAULT)
+    }
+
+    /// One-time migration for deployments that predate the per-user rewrite:
+    /// if a global count is still sitting under the legacy `COUNTER` instance
+    /// entry, consume it as the starting state for the first user who
+    /// increments after the upgrade, then remove the legacy entry so it's
+    /// only ever claimed once.
+    ///
+    /// The old global counter has no natural per-user owner, so this hands it
+    /// to whichever address happens to call `increment`/`increment_by` first
+    /// after the upgrade. `get_state`, `decrement` and `reset` never read the
+    /// legacy entry, so until that first increment the legacy value is not
+    /// visible through any of them.
+    fn migrate_legacy_counter(env: &Env) -> Option<CounterState> {
+        let legacy: u32 = env.storage().instance().get(&LEGACY_COUNTER)?;
+        env.storage().instance().remove(&LEGACY_COUNTER);
+        Some(CounterState {
+            count: legacy,
+            last_incr: 0,
+            updated_ledger: env.ledger().sequence(),
+        })
+    }
+
+    /// Extends `key`'s TTL, and the contract instance's own TTL, using the
+    /// admin-configured policy - falling back to the original
+    /// `extend_ttl(50, 100)` defaults if none has been set. Only bumps the
+    /// `persistent()` entry when `config.counters_persistent` says that's
+    /// where `key` actually lives; the instance TTL is always bumped, since
+    /// Admin and StorageConfig live there regardless of the counter tier.
+    fn bump_ttl(env: &Env, key: &DataKey, config: &StorageConfig) {
+        if config.counters_persistent {
+            env.storage()
+                .persistent()
+                .extend_ttl(key, config.threshold, config.extend_to);
+        }
+        env.storage()
+            .instance()
+            .extend_ttl(config.threshold, config.extend_to);
+    }
+}
+
+// The test module is included below, but only compiled when running tests.
+mod test;